@@ -0,0 +1,286 @@
+//! Dirty-file reporting and settings-invalidation for `build`.
+//!
+//! `Project::compile` already skips re-invoking solc when nothing in the
+//! project changed, but solc compiles a project as a single unit -- there
+//! is no "recompile just these files" mode to call into, so this module
+//! does NOT scope the actual solc invocation down to a subset of files.
+//! What it does provide:
+//!
+//! - a per-file content fingerprint, so `build` can print which files
+//!   changed (and, transitively, which importers of a changed file are
+//!   affected) versus which are untouched since the last run;
+//! - a settings fingerprint (remappings, EVM version) folded into every
+//!   file's record, so a settings change that solc's own cache wouldn't
+//!   notice (no file content changed) still shows up as "dirty" here.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+const CACHE_FILE_NAME: &str = "foundry-build-cache.json";
+
+/// One source file's fingerprint as of its last successful compile.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct FileFingerprint {
+    content_hash: String,
+    settings_hash: String,
+}
+
+/// Persisted per-file fingerprints, keyed by absolute source path.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BuildCache {
+    files: BTreeMap<PathBuf, FileFingerprint>,
+}
+
+/// The result of comparing a project's current sources against the
+/// persisted cache.
+#[derive(Debug, Default)]
+pub struct DirtyFiles {
+    pub dirty: BTreeSet<PathBuf>,
+    pub cached: BTreeSet<PathBuf>,
+}
+
+impl BuildCache {
+    fn path(artifacts_dir: &Path) -> PathBuf {
+        artifacts_dir.join(CACHE_FILE_NAME)
+    }
+
+    /// Loads the cache from `artifacts_dir`, or an empty one if it's
+    /// missing or unreadable -- a corrupt cache just means everything is
+    /// reported dirty, never a hard error.
+    pub fn load(artifacts_dir: &Path) -> BuildCache {
+        std::fs::read_to_string(Self::path(artifacts_dir))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn write(&self, artifacts_dir: &Path) -> eyre::Result<()> {
+        std::fs::create_dir_all(artifacts_dir)?;
+        std::fs::write(Self::path(artifacts_dir), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Splits `sources` into those whose content or settings changed since
+    /// they were last recorded, plus anything that (transitively) imports
+    /// one of those files (`dirty`), versus everything else (`cached`).
+    pub fn diff(&self, sources: &BTreeMap<PathBuf, String>, settings_hash: &str) -> DirtyFiles {
+        let mut dirty = BTreeSet::new();
+        for (path, content) in sources {
+            let fingerprint = FileFingerprint {
+                content_hash: hash_content(content),
+                settings_hash: settings_hash.to_string(),
+            };
+            if self.files.get(path) != Some(&fingerprint) {
+                dirty.insert(path.clone());
+            }
+        }
+
+        // propagate dirtiness to anything that imports a dirty file --
+        // even though we don't recompile just the affected subset, the
+        // report should still reflect what actually needs re-checking.
+        let imports = import_graph(sources);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (path, deps) in &imports {
+                if dirty.contains(path) {
+                    continue
+                }
+                if deps.iter().any(|dep| dirty.contains(dep)) {
+                    dirty.insert(path.clone());
+                    changed = true;
+                }
+            }
+        }
+
+        let cached = sources.keys().filter(|path| !dirty.contains(*path)).cloned().collect();
+        DirtyFiles { dirty, cached }
+    }
+
+    /// Records the fingerprint of every successfully compiled source.
+    pub fn update(&mut self, sources: &BTreeMap<PathBuf, String>, settings_hash: &str) {
+        for (path, content) in sources {
+            self.files.insert(
+                path.clone(),
+                FileFingerprint {
+                    content_hash: hash_content(content),
+                    settings_hash: settings_hash.to_string(),
+                },
+            );
+        }
+    }
+}
+
+/// A stable fingerprint of the compiler settings that affect every file's
+/// output, so a changed remapping or EVM version invalidates the cache
+/// even when every file's content is untouched.
+pub fn settings_hash(evm_version: &str, remappings: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(evm_version.as_bytes());
+    for remapping in remappings {
+        hasher.update(remapping.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+fn hash_content(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// A best-effort `import "..."` graph: for each source, the other sources
+/// (from the same set) it directly imports. Used only to propagate
+/// dirtiness to importers for reporting purposes.
+fn import_graph(sources: &BTreeMap<PathBuf, String>) -> BTreeMap<PathBuf, Vec<PathBuf>> {
+    sources
+        .iter()
+        .map(|(path, content)| {
+            let deps = content
+                .lines()
+                .map(str::trim)
+                .filter(|line| line.starts_with("import"))
+                .filter_map(extract_quoted)
+                .filter_map(|import_path| resolve_import(path, &import_path, sources))
+                .collect();
+            (path.clone(), deps)
+        })
+        .collect()
+}
+
+/// Pulls the first single- or double-quoted substring out of an import
+/// line, e.g. `import "./Lib.sol";` -> `./Lib.sol`.
+fn extract_quoted(line: &str) -> Option<String> {
+    let start = line.find(['"', '\''])?;
+    let quote = line.as_bytes()[start] as char;
+    let rest = &line[start + 1..];
+    let end = rest.find(quote)?;
+    Some(rest[..end].to_string())
+}
+
+/// Resolves a relative import against the importing file's directory and
+/// matches it against the known source set. Remapped (non-relative)
+/// imports are out of scope for this best-effort report.
+fn resolve_import(
+    importer: &Path,
+    import_path: &str,
+    sources: &BTreeMap<PathBuf, String>,
+) -> Option<PathBuf> {
+    let candidate = normalize(&importer.parent()?.join(import_path));
+    sources.keys().find(|path| normalize(path) == candidate).cloned()
+}
+
+/// Collapses `.`/`..` components lexically, without touching the
+/// filesystem -- these paths aren't guaranteed to exist as given (e.g. in
+/// tests), so `Path::canonicalize` isn't an option here.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            std::path::Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Prints the dirty-vs-cached report requested for `build`.
+pub fn report(dirty: &DirtyFiles, root: &Path) {
+    if dirty.dirty.is_empty() {
+        println!("all {} files unchanged, reused from cache.", dirty.cached.len());
+        return
+    }
+    println!("recompiling {} file(s):", dirty.dirty.len());
+    for path in &dirty.dirty {
+        println!("  {}", path.strip_prefix(root).unwrap_or(path).display());
+    }
+    if !dirty.cached.is_empty() {
+        println!("reusing {} cached file(s):", dirty.cached.len());
+        for path in &dirty.cached {
+            println!("  {}", path.strip_prefix(root).unwrap_or(path).display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(path: &str, content: &str) -> (PathBuf, String) {
+        (PathBuf::from(path), content.to_string())
+    }
+
+    #[test]
+    fn first_run_marks_everything_dirty() {
+        let cache = BuildCache::default();
+        let sources = BTreeMap::from([source("/p/src/A.sol", "contract A {}")]);
+
+        let dirty = cache.diff(&sources, "settings-1");
+
+        assert!(dirty.dirty.contains(&PathBuf::from("/p/src/A.sol")));
+        assert!(dirty.cached.is_empty());
+    }
+
+    #[test]
+    fn unchanged_file_is_reported_as_cached() {
+        let mut cache = BuildCache::default();
+        let sources = BTreeMap::from([source("/p/src/A.sol", "contract A {}")]);
+        cache.update(&sources, "settings-1");
+
+        let dirty = cache.diff(&sources, "settings-1");
+
+        assert!(dirty.dirty.is_empty());
+        assert!(dirty.cached.contains(&PathBuf::from("/p/src/A.sol")));
+    }
+
+    #[test]
+    fn changed_content_is_dirty() {
+        let mut cache = BuildCache::default();
+        let sources = BTreeMap::from([source("/p/src/A.sol", "contract A {}")]);
+        cache.update(&sources, "settings-1");
+
+        let edited = BTreeMap::from([source("/p/src/A.sol", "contract A { uint x; }")]);
+        let dirty = cache.diff(&edited, "settings-1");
+
+        assert!(dirty.dirty.contains(&PathBuf::from("/p/src/A.sol")));
+    }
+
+    #[test]
+    fn changed_settings_invalidate_unchanged_content() {
+        let mut cache = BuildCache::default();
+        let sources = BTreeMap::from([source("/p/src/A.sol", "contract A {}")]);
+        cache.update(&sources, "settings-1");
+
+        let dirty = cache.diff(&sources, "settings-2");
+
+        assert!(dirty.dirty.contains(&PathBuf::from("/p/src/A.sol")));
+    }
+
+    #[test]
+    fn dirty_file_marks_its_importers_dirty_too() {
+        let mut cache = BuildCache::default();
+        let sources = BTreeMap::from([
+            source("/p/src/Lib.sol", "contract Lib {}"),
+            source("/p/src/A.sol", "import \"./Lib.sol\";\ncontract A {}"),
+        ]);
+        cache.update(&sources, "settings-1");
+
+        let mut edited = sources;
+        edited.insert(PathBuf::from("/p/src/Lib.sol"), "contract Lib { uint x; }".to_string());
+
+        let dirty = cache.diff(&edited, "settings-1");
+
+        assert!(dirty.dirty.contains(&PathBuf::from("/p/src/Lib.sol")));
+        assert!(
+            dirty.dirty.contains(&PathBuf::from("/p/src/A.sol")),
+            "A.sol imports Lib.sol and should be marked dirty transitively"
+        );
+    }
+}
@@ -1,23 +1,20 @@
 //! build command
 
-use ethers::{
-    solc::{
-        remappings::Remapping, MinimalCombinedArtifacts, Project, ProjectCompileOutput,
-        ProjectPathsConfig,
-    },
-    types::Address,
+use ethers::solc::{
+    remappings::Remapping, ArtifactOutput, HardhatArtifacts, MinimalCombinedArtifacts, Project,
+    ProjectCompileOutput, ProjectPathsConfig,
 };
-use std::{path::PathBuf, str::FromStr};
-
-use crate::{utils::find_git_root_path, Cmd};
-#[cfg(feature = "evmodin-evm")]
-use evmodin::util::mocked_host::MockedHost;
-#[cfg(feature = "evmodin-evm")]
-use evmodin::Revision;
-#[cfg(feature = "sputnik-evm")]
-use sputnik::backend::MemoryVicinity;
-#[cfg(feature = "sputnik-evm")]
-use sputnik::Config;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    config::{Config as FoundryConfig, Profile, DEFAULT_PROFILE},
+    utils::find_git_root_path,
+    Cmd,
+};
+use evm_adapters::EvmVersion;
 use structopt::StructOpt;
 
 #[derive(Debug, Clone, StructOpt)]
@@ -47,8 +44,8 @@ pub struct BuildArgs {
     #[structopt(help = "path to where the contract artifacts are stored", long = "out", short)]
     pub out_path: Option<PathBuf>,
 
-    #[structopt(help = "choose the evm version", long, default_value = "london")]
-    pub evm_version: EvmVersion,
+    #[structopt(help = "choose the evm version", long)]
+    pub evm_version: Option<EvmVersion>,
 
     #[structopt(
         help = "if set to true, skips auto-detecting solc and uses what is in the user's $PATH ",
@@ -61,269 +58,374 @@ pub struct BuildArgs {
         long
     )]
     pub force: bool,
-}
 
-impl Cmd for BuildArgs {
-    type Output = ProjectCompileOutput<MinimalCombinedArtifacts>;
-    fn run(self) -> eyre::Result<Self::Output> {
-        let project = Project::try_from(&self)?;
-        let output = project.compile()?;
-        if output.has_compiler_errors() {
-            // return the diagnostics error back to the user.
-            eyre::bail!(output.to_string())
-        } else if output.is_unchanged() {
-            println!("no files changed, compilation skippped.");
-        } else {
-            println!("success.");
-        }
-        Ok(output)
-    }
-}
+    #[structopt(
+        help = "skip reading the build cache, recompiling every file (unlike --force, does not delete the cache or artifacts)",
+        long
+    )]
+    pub no_cache: bool,
 
-impl std::convert::TryFrom<&BuildArgs> for Project {
-    type Error = eyre::Error;
+    #[structopt(
+        help = "the foundry.toml profile to use",
+        long,
+        env = "FOUNDRY_PROFILE"
+    )]
+    pub profile: Option<String>,
 
-    /// Defaults to converting to DAppTools-style repo layout, but can be customized.
-    fn try_from(opts: &BuildArgs) -> eyre::Result<Project> {
-        // 1. Set the root dir
-        let root = opts.root.clone().unwrap_or_else(|| {
-            find_git_root_path().unwrap_or_else(|_| std::env::current_dir().unwrap())
-        });
-        let root = std::fs::canonicalize(&root)?;
-
-        // 2. Set the contracts dir
-        let contracts = if let Some(ref contracts) = opts.contracts {
-            root.join(contracts)
-        } else {
-            root.join("src")
-        };
+    #[structopt(
+        help = "never attempt to download a missing solc version, erroring instead",
+        long,
+        env = "FOUNDRY_OFFLINE"
+    )]
+    pub offline: bool,
 
-        // 3. Set the output dir
-        let artifacts = if let Some(ref artifacts) = opts.out_path {
-            root.join(artifacts)
-        } else {
-            root.join("out")
-        };
+    #[structopt(
+        help = "the artifacts layout to write to `--out`: `combined` (DappTools) or `hardhat`",
+        long,
+        default_value = "combined"
+    )]
+    pub artifacts_format: ArtifactsFormat,
 
-        // 4. Set where the libraries are going to be read from
-        // default to the lib path being the `lib/` dir
-        let lib_paths =
-            if opts.lib_paths.is_empty() { vec![root.join("lib")] } else { opts.lib_paths.clone() };
+    #[structopt(
+        help = "a solc error code to downgrade from a hard failure to a suppressed warning, e.g. SPDX-license or unused-parameter codes pulled in from lib/ dependencies (may be repeated)",
+        long
+    )]
+    pub ignored_error_codes: Vec<u64>,
+}
 
-        // get all the remappings corresponding to the lib paths
-        let mut remappings: Vec<_> =
-            lib_paths.iter().map(|path| Remapping::find_many(&path).unwrap()).flatten().collect();
+/// The artifact layout written to `--out`.
+#[derive(Debug, Clone, Copy)]
+pub enum ArtifactsFormat {
+    /// A single combined-JSON file, DappTools-style.
+    Combined,
+    /// A Hardhat-style `artifacts/<Contract>.sol/<Contract>.json` per contract.
+    Hardhat,
+}
 
-        // extend them with the once manually provided in the opts
-        remappings.extend_from_slice(&opts.remappings);
+impl FromStr for ArtifactsFormat {
+    type Err = eyre::Error;
 
-        // extend them with the one via the env vars
-        if let Some(ref env) = opts.remappings_env {
-            remappings.extend(remappings_from_newline(env))
-        }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "combined" => ArtifactsFormat::Combined,
+            "hardhat" => ArtifactsFormat::Hardhat,
+            other => eyre::bail!("unsupported artifacts format: {}", other),
+        })
+    }
+}
 
-        // extend them with the one via the requirements.txt
-        if let Ok(ref remap) = std::fs::read_to_string(root.join("remappings.txt")) {
-            remappings.extend(remappings_from_newline(remap))
-        }
+/// The compiled output produced by [`BuildArgs::run`], shaped according to
+/// the selected [`ArtifactsFormat`]. Downstream commands that also want to
+/// pick a format should match on this the same way.
+pub enum BuildOutput {
+    Combined(ProjectCompileOutput<MinimalCombinedArtifacts>),
+    Hardhat(ProjectCompileOutput<HardhatArtifacts>),
+}
 
-        // helper function for parsing newline-separated remappings
-        fn remappings_from_newline(remappings: &str) -> impl Iterator<Item = Remapping> + '_ {
-            remappings.split('\n').filter(|x| !x.is_empty()).map(|x| {
-                Remapping::from_str(x)
-                    .unwrap_or_else(|_| panic!("could not parse remapping: {}", x))
-            })
+impl BuildOutput {
+    pub fn has_compiler_errors(&self) -> bool {
+        match self {
+            BuildOutput::Combined(out) => out.has_compiler_errors(),
+            BuildOutput::Hardhat(out) => out.has_compiler_errors(),
         }
+    }
 
-        // remove any potential duplicates
-        remappings.sort_unstable();
-        remappings.dedup();
-
-        // build the path
-        let mut paths_builder =
-            ProjectPathsConfig::builder().root(&root).sources(contracts).artifacts(artifacts);
-
-        if !remappings.is_empty() {
-            paths_builder = paths_builder.remappings(remappings);
+    pub fn is_unchanged(&self) -> bool {
+        match self {
+            BuildOutput::Combined(out) => out.is_unchanged(),
+            BuildOutput::Hardhat(out) => out.is_unchanged(),
         }
+    }
 
-        let paths = paths_builder.build()?;
-
-        // build the project w/ allowed paths = root and all the libs
-        let mut builder =
-            Project::builder().paths(paths).allowed_path(&root).allowed_paths(lib_paths);
-
-        if opts.no_auto_detect {
-            builder = builder.no_auto_detect();
+    /// Prints how many diagnostics were downgraded by `--ignored-error-codes`,
+    /// so suppressed warnings aren't silently lost.
+    pub fn print_suppressed_warnings(&self, ignored_error_codes: &[u64]) {
+        if ignored_error_codes.is_empty() {
+            return
         }
-
-        let project = builder.build()?;
-
-        // if `--force` is provided, it proceeds to remove the cache
-        // and recompile the contracts.
-        if opts.force {
-            project.cleanup()?;
+        let suppressed = match self {
+            BuildOutput::Combined(out) => count_suppressed(out, ignored_error_codes),
+            BuildOutput::Hardhat(out) => count_suppressed(out, ignored_error_codes),
+        };
+        if suppressed > 0 {
+            println!("suppressed {} solc warning(s) via --ignored-error-codes", suppressed);
         }
-
-        Ok(project)
     }
 }
 
-#[derive(Clone, Debug)]
-pub enum EvmType {
-    #[cfg(feature = "sputnik-evm")]
-    Sputnik,
-    #[cfg(feature = "evmodin-evm")]
-    EvmOdin,
+fn count_suppressed<Artifacts>(
+    output: &ProjectCompileOutput<Artifacts>,
+    ignored_error_codes: &[u64],
+) -> usize {
+    count_matching_codes(output.output().errors.iter().map(|error| error.error_code), ignored_error_codes)
 }
 
-impl FromStr for EvmType {
-    type Err = eyre::Error;
+/// The pure filtering rule behind [`count_suppressed`]: a diagnostic
+/// counts as suppressed only if it carries an error code and that code is
+/// in `ignored_error_codes`. Split out so the rule can be tested without
+/// constructing a real `ProjectCompileOutput`.
+fn count_matching_codes(
+    codes: impl Iterator<Item = Option<u64>>,
+    ignored_error_codes: &[u64],
+) -> usize {
+    codes.filter(|code| code.map_or(false, |code| ignored_error_codes.contains(&code))).count()
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s.to_lowercase().as_str() {
-            #[cfg(feature = "sputnik-evm")]
-            "sputnik" => EvmType::Sputnik,
-            #[cfg(feature = "evmodin-evm")]
-            "evmodin" => EvmType::EvmOdin,
-            other => eyre::bail!("unknown EVM type {}", other),
-        })
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_codes_present_in_the_ignore_list() {
+        let codes = vec![Some(1878), Some(2072), Some(1878)];
+        assert_eq!(count_matching_codes(codes.into_iter(), &[1878]), 2);
+    }
+
+    #[test]
+    fn does_not_count_codes_absent_from_the_ignore_list() {
+        let codes = vec![Some(2072), Some(3420)];
+        assert_eq!(count_matching_codes(codes.into_iter(), &[1878]), 0);
+    }
+
+    #[test]
+    fn does_not_count_diagnostics_without_an_error_code() {
+        let codes = vec![None, Some(1878)];
+        assert_eq!(count_matching_codes(codes.into_iter(), &[1878]), 1);
     }
-}
 
-#[derive(Clone, Debug)]
-pub enum EvmVersion {
-    Frontier,
-    Istanbul,
-    Berlin,
-    London,
+    #[test]
+    fn an_empty_ignore_list_suppresses_nothing() {
+        let codes = vec![Some(1878), Some(2072)];
+        assert_eq!(count_matching_codes(codes.into_iter(), &[]), 0);
+    }
 }
 
-impl EvmVersion {
-    #[cfg(feature = "sputnik-evm")]
-    pub fn sputnik_cfg(self) -> Config {
-        use EvmVersion::*;
+impl std::fmt::Display for BuildOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Frontier => Config::frontier(),
-            Istanbul => Config::istanbul(),
-            Berlin => Config::berlin(),
-            London => Config::london(),
+            BuildOutput::Combined(out) => out.fmt(f),
+            BuildOutput::Hardhat(out) => out.fmt(f),
         }
     }
+}
 
-    #[cfg(feature = "evmodin-evm")]
-    pub fn evmodin_cfg(self) -> Revision {
-        use EvmVersion::*;
-        match self {
-            Frontier => Revision::Frontier,
-            Istanbul => Revision::Istanbul,
-            Berlin => Revision::Berlin,
-            London => Revision::London,
+impl Cmd for BuildArgs {
+    type Output = BuildOutput;
+    fn run(self) -> eyre::Result<Self::Output> {
+        let (output, ignored_error_codes) = match self.artifacts_format {
+            ArtifactsFormat::Combined => {
+                let (output, codes) = compile::<MinimalCombinedArtifacts>(&self)?;
+                (BuildOutput::Combined(output), codes)
+            }
+            ArtifactsFormat::Hardhat => {
+                let (output, codes) = compile::<HardhatArtifacts>(&self)?;
+                (BuildOutput::Hardhat(output), codes)
+            }
+        };
+        if output.has_compiler_errors() {
+            // return the diagnostics error back to the user.
+            eyre::bail!(output.to_string())
+        } else if output.is_unchanged() {
+            println!("no files changed, compilation skippped.");
+        } else {
+            println!("success.");
         }
+        output.print_suppressed_warnings(&ignored_error_codes);
+        Ok(output)
     }
 }
 
-impl FromStr for EvmVersion {
-    type Err = eyre::Error;
+/// Resolves the project root and loads the selected `foundry.toml` profile.
+/// Both [`build_project`] and [`compile`] need this, so it's computed once
+/// per build invocation and passed around rather than each re-reading
+/// `foundry.toml` from disk independently.
+fn resolve_root_and_profile(opts: &BuildArgs) -> eyre::Result<(PathBuf, Profile)> {
+    let root = opts.root.clone().unwrap_or_else(|| {
+        find_git_root_path().unwrap_or_else(|_| std::env::current_dir().unwrap())
+    });
+    let root = std::fs::canonicalize(&root)?;
+
+    // load the selected foundry.toml profile; an absent file or profile
+    // just yields defaults, so this is never an error on its own.
+    let profile_name = opts.profile.clone().unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    let profile = FoundryConfig::load(&root)?.profile(&profile_name);
+
+    Ok((root, profile))
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use EvmVersion::*;
-        Ok(match s.to_lowercase().as_str() {
-            "frontier" => Frontier,
-            "istanbul" => Istanbul,
-            "berlin" => Berlin,
-            "london" => London,
-            _ => eyre::bail!("unsupported evm version: {}", s),
-        })
+/// Builds the project and compiles it, generic over the artifacts layout.
+/// Returns the merged `--ignored-error-codes` (computed once by
+/// [`build_project`]) alongside the output so `run` can print the
+/// suppressed-warning summary without re-deriving it.
+fn compile<Artifacts: ArtifactOutput + Default>(
+    opts: &BuildArgs,
+) -> eyre::Result<(ProjectCompileOutput<Artifacts>, Vec<u64>)> {
+    let (root, profile) = resolve_root_and_profile(opts)?;
+    let (project, remappings, ignored_error_codes): (Project<Artifacts>, Vec<String>, Vec<u64>) =
+        build_project(opts, &root, &profile)?;
+    let no_auto_detect = opts.no_auto_detect || profile.no_auto_detect.unwrap_or(false);
+
+    let evm_version = opts
+        .evm_version
+        .clone()
+        .or_else(|| profile.evm_version.clone())
+        .unwrap_or(EvmVersion::London);
+    // use the fully-merged remapping set (not just `opts.remappings`) so a
+    // changed lib path, profile remapping, or `remappings.txt` entry
+    // invalidates the cache even when no file's content changed.
+    let settings_hash = crate::cache::settings_hash(&evm_version.to_string(), &remappings);
+    let sources: std::collections::BTreeMap<_, _> =
+        ethers::solc::artifacts::Source::read_all_files(project.paths.input_files())?
+            .into_iter()
+            .map(|(path, source)| (path, source.content))
+            .collect();
+
+    let mut cache = if opts.no_cache {
+        crate::cache::BuildCache::default()
+    } else {
+        crate::cache::BuildCache::load(&project.paths.artifacts)
+    };
+    let dirty = cache.diff(&sources, &settings_hash);
+    crate::cache::report(&dirty, &project.paths.root);
+
+    // `--no-auto-detect` (or a profile setting it) opts out of per-file
+    // version resolution and just uses whatever solc is on the user's
+    // $PATH -- mirrors the merged value `build_project` applied to the
+    // solc builder, so the two never disagree.
+    let output = if no_auto_detect {
+        project.compile()?
+    } else {
+        crate::compile::compile_with_version_detection(&project, opts.offline)?
+    };
+
+    if !opts.no_cache && !output.has_compiler_errors() {
+        cache.update(&sources, &settings_hash);
+        cache.write(&project.paths.artifacts)?;
     }
+
+    Ok((output, ignored_error_codes))
 }
 
-#[derive(Debug, Clone, StructOpt)]
-pub struct Env {
-    // structopt does not let use `u64::MAX`:
-    // https://doc.rust-lang.org/std/primitive.u64.html#associatedconstant.MAX
-    #[structopt(help = "the block gas limit", long, default_value = "18446744073709551615")]
-    pub gas_limit: u64,
+impl<Artifacts: ArtifactOutput + Default> std::convert::TryFrom<&BuildArgs> for Project<Artifacts> {
+    type Error = eyre::Error;
 
-    #[structopt(help = "the chainid opcode value", long, default_value = "1")]
-    pub chain_id: u64,
+    /// Defaults to converting to DAppTools-style repo layout, but can be customized.
+    fn try_from(opts: &BuildArgs) -> eyre::Result<Project<Artifacts>> {
+        let (root, profile) = resolve_root_and_profile(opts)?;
+        let (project, ..) = build_project(opts, &root, &profile)?;
+        Ok(project)
+    }
+}
 
-    #[structopt(help = "the tx.gasprice value during EVM execution", long, default_value = "0")]
-    pub gas_price: u64,
+/// Does the actual project construction once `root`/`profile` have been
+/// resolved by the caller. Also returns the merged values `compile`
+/// needs afterwards -- the fully-merged remapping strings (lib-derived +
+/// profile + CLI + env + `remappings.txt`) and `--ignored-error-codes`
+/// (CLI + profile) -- so neither has to be re-derived and risk drifting
+/// from what was actually passed to the solc builder above.
+fn build_project<Artifacts: ArtifactOutput + Default>(
+    opts: &BuildArgs,
+    root: &Path,
+    profile: &Profile,
+) -> eyre::Result<(Project<Artifacts>, Vec<String>, Vec<u64>)> {
+    // 2. Set the contracts dir
+    let contracts = opts
+        .contracts
+        .clone()
+        .or_else(|| profile.src.clone())
+        .map(|contracts| root.join(contracts))
+        .unwrap_or_else(|| root.join("src"));
+
+    // 3. Set the output dir
+    let artifacts = opts
+        .out_path
+        .clone()
+        .or_else(|| profile.out.clone())
+        .map(|artifacts| root.join(artifacts))
+        .unwrap_or_else(|| root.join("out"));
+
+    // 4. Set where the libraries are going to be read from
+    // default to the lib path being the `lib/` dir
+    let lib_paths = if !opts.lib_paths.is_empty() {
+        opts.lib_paths.clone()
+    } else if let Some(ref libs) = profile.libs {
+        libs.clone()
+    } else {
+        vec![root.join("lib")]
+    };
+
+    // get all the remappings corresponding to the lib paths
+    let mut remappings: Vec<_> =
+        lib_paths.iter().map(|path| Remapping::find_many(&path).unwrap()).flatten().collect();
+
+    // extend them with the ones set in the foundry.toml profile
+    remappings.extend(profile.remappings());
+
+    // extend them with the once manually provided in the opts
+    remappings.extend_from_slice(&opts.remappings);
+
+    // extend them with the one via the env vars
+    if let Some(ref env) = opts.remappings_env {
+        remappings.extend(remappings_from_newline(env))
+    }
 
-    #[structopt(help = "the base fee in a block", long, default_value = "0")]
-    pub block_base_fee_per_gas: u64,
+    // extend them with the one via the requirements.txt
+    if let Ok(ref remap) = std::fs::read_to_string(root.join("remappings.txt")) {
+        remappings.extend(remappings_from_newline(remap))
+    }
 
-    #[structopt(
-        help = "the tx.origin value during EVM execution",
-        long,
-        default_value = "0x0000000000000000000000000000000000000000"
-    )]
-    pub tx_origin: Address,
+    // helper function for parsing newline-separated remappings
+    fn remappings_from_newline(remappings: &str) -> impl Iterator<Item = Remapping> + '_ {
+        remappings.split('\n').filter(|x| !x.is_empty()).map(|x| {
+            Remapping::from_str(x).unwrap_or_else(|_| panic!("could not parse remapping: {}", x))
+        })
+    }
 
-    #[structopt(
-    help = "the block.coinbase value during EVM execution",
-    long,
-    // TODO: It'd be nice if we could use Address::zero() here.
-    default_value = "0x0000000000000000000000000000000000000000"
-    )]
-    pub block_coinbase: Address,
-    #[structopt(
-        help = "the block.timestamp value during EVM execution",
-        long,
-        default_value = "0",
-        env = "DAPP_TEST_TIMESTAMP"
-    )]
-    pub block_timestamp: u64,
+    // remove any potential duplicates
+    remappings.sort_unstable();
+    remappings.dedup();
 
-    #[structopt(help = "the block.number value during EVM execution", long, default_value = "0")]
-    #[structopt(env = "DAPP_TEST_NUMBER")]
-    pub block_number: u64,
+    let remapping_strings: Vec<String> = remappings.iter().map(|r| r.to_string()).collect();
 
-    #[structopt(
-        help = "the block.difficulty value during EVM execution",
-        long,
-        default_value = "0"
-    )]
-    pub block_difficulty: u64,
+    // build the path
+    let mut paths_builder =
+        ProjectPathsConfig::builder().root(root).sources(contracts).artifacts(artifacts);
 
-    #[structopt(help = "the block.gaslimit value during EVM execution", long)]
-    pub block_gas_limit: Option<u64>,
-    // TODO: Add configuration option for base fee.
-}
+    if !remappings.is_empty() {
+        paths_builder = paths_builder.remappings(remappings);
+    }
 
-impl Env {
-    #[cfg(feature = "sputnik-evm")]
-    pub fn sputnik_state(&self) -> MemoryVicinity {
-        MemoryVicinity {
-            chain_id: self.chain_id.into(),
-
-            gas_price: self.gas_price.into(),
-            origin: self.tx_origin,
-
-            block_coinbase: self.block_coinbase,
-            block_number: self.block_number.into(),
-            block_timestamp: self.block_timestamp.into(),
-            block_difficulty: self.block_difficulty.into(),
-            block_base_fee_per_gas: self.block_base_fee_per_gas.into(),
-            block_gas_limit: self.block_gas_limit.unwrap_or(self.gas_limit).into(),
-            block_hashes: Vec::new(),
-        }
+    let paths = paths_builder.build()?;
+
+    // build the project w/ allowed paths = root and all the libs
+    let mut builder = Project::builder().paths(paths).allowed_path(root).allowed_paths(lib_paths);
+
+    let no_auto_detect = opts.no_auto_detect || profile.no_auto_detect.unwrap_or(false);
+    if no_auto_detect {
+        builder = builder.no_auto_detect();
     }
 
-    #[cfg(feature = "evmodin-evm")]
-    pub fn evmodin_state(&self) -> MockedHost {
-        let mut host = MockedHost::default();
+    // `--no-cache` skips solc's own cache too, so every file is recompiled
+    if opts.no_cache {
+        builder = builder.ephemeral();
+    }
 
-        host.tx_context.chain_id = self.chain_id.into();
-        host.tx_context.tx_gas_price = self.gas_price.into();
-        host.tx_context.tx_origin = self.tx_origin;
-        host.tx_context.block_coinbase = self.block_coinbase;
-        host.tx_context.block_number = self.block_number;
-        host.tx_context.block_timestamp = self.block_timestamp;
-        host.tx_context.block_difficulty = self.block_difficulty.into();
-        host.tx_context.block_gas_limit = self.block_gas_limit.unwrap_or(self.gas_limit);
+    // downgrade known-noisy solc codes (SPDX license, unused params,
+    // shadowing pulled in from `lib/`, etc.) from a hard failure to a
+    // suppressed warning.
+    let mut ignored_error_codes = opts.ignored_error_codes.clone();
+    ignored_error_codes.extend(profile.ignored_error_codes.clone().unwrap_or_default());
+    for code in &ignored_error_codes {
+        builder = builder.ignore_error_code(*code);
+    }
+
+    let project = builder.build()?;
 
-        host
+    // if `--force` is provided, it proceeds to remove the cache
+    // and recompile the contracts.
+    if opts.force {
+        project.cleanup()?;
     }
+
+    Ok((project, remapping_strings, ignored_error_codes))
 }
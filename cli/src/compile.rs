@@ -0,0 +1,156 @@
+//! Multi-version solc resolution.
+//!
+//! Rather than compiling an entire project with a single solc binary, this
+//! reads each source file's `pragma solidity` statement, groups files by the
+//! newest installed release that satisfies it (or, when none is installed,
+//! the newest known release), and compiles each group separately, merging
+//! the artifacts into one [`ProjectCompileOutput`]. This lets a repo mix
+//! e.g. `0.6.x` libraries under `lib/` with `0.8.x` contracts.
+
+use ethers::solc::{
+    artifacts::Source, utils::find_version_pragma, ArtifactOutput, Project, ProjectCompileOutput,
+    Solc,
+};
+use semver::{Version, VersionReq};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// Compiles `project`, resolving one solc version per source file from its
+/// pragma instead of a single project-wide version. Generic over the
+/// project's [`ArtifactOutput`] so it works the same whether `--out` is
+/// written as combined-JSON or Hardhat-style artifacts. Requires
+/// `Artifacts: Default` -- merging each version group's output starts
+/// from an empty [`ProjectCompileOutput`], same as every other
+/// `ArtifactOutput` implementor used elsewhere in this crate.
+///
+/// When `offline` is `true`, a source requiring a version that isn't
+/// already installed is a hard error naming the missing version instead of
+/// triggering a network install, so CI in sandboxed environments stays
+/// deterministic.
+pub fn compile_with_version_detection<Artifacts: ArtifactOutput + Default>(
+    project: &Project<Artifacts>,
+    offline: bool,
+) -> eyre::Result<ProjectCompileOutput<Artifacts>> {
+    let sources = Source::read_all_files(project.paths.input_files())?;
+
+    let mut grouped: BTreeMap<Version, Vec<PathBuf>> = BTreeMap::new();
+    for (path, source) in sources.iter() {
+        let req = find_version_pragma(&source.content).ok_or_else(|| {
+            eyre::eyre!("{} has no `pragma solidity` version statement", path.display())
+        })?;
+        let version = resolve_version(&req, offline)?;
+        grouped.entry(version).or_default().push(path.clone());
+    }
+
+    // one `ProjectCompileOutput` per version group, merged via `extend` --
+    // relies on `Project::compile_with_version`/`ProjectCompileOutput::extend`
+    // being present on the `ethers::solc` version this crate targets.
+    let mut output = ProjectCompileOutput::default();
+    for (version, paths) in grouped {
+        let solc = Solc::find_or_install_svm_version(version.to_string())?;
+        output.extend(project.compile_with_version(&solc, paths)?);
+    }
+
+    Ok(output)
+}
+
+/// Picks the newest installed release satisfying `req`, falling back to the
+/// newest known release only when none is installed. Already-installed
+/// versions are always preferred over a fresh download.
+fn resolve_version(req: &VersionReq, offline: bool) -> eyre::Result<Version> {
+    let (version, needs_install) =
+        pick_version(req, &Solc::installed_versions(), &Solc::all_versions(), offline)?;
+    if needs_install {
+        Solc::blocking_install(&version)?;
+    }
+    Ok(version)
+}
+
+/// The pure decision behind [`resolve_version`], taking the installed/known
+/// version lists as plain arguments so the preference and offline-error
+/// rules can be tested without touching the real svm install directory or
+/// the network. Returns whether the caller still needs to install `version`.
+fn pick_version(
+    req: &VersionReq,
+    installed: &[Version],
+    known: &[Version],
+    offline: bool,
+) -> eyre::Result<(Version, bool)> {
+    if let Some(version) = installed.iter().filter(|v| req.matches(v)).max() {
+        return Ok((version.clone(), false))
+    }
+
+    if offline {
+        eyre::bail!(
+            "no installed solc version satisfies `{}` and --offline is set; install one with `svm install <version>`",
+            req
+        )
+    }
+
+    let version = known
+        .iter()
+        .filter(|v| req.matches(v))
+        .max()
+        .cloned()
+        .ok_or_else(|| eyre::eyre!("no known solc release satisfies `{}`", req))?;
+    Ok((version, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(s: &str) -> VersionReq {
+        VersionReq::parse(s).unwrap()
+    }
+
+    fn version(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    #[test]
+    fn prefers_an_installed_version_over_a_newer_download() {
+        let installed = vec![version("0.8.10")];
+        let known = vec![version("0.8.10"), version("0.8.17")];
+
+        let (picked, needs_install) = pick_version(&req("^0.8.0"), &installed, &known, false).unwrap();
+
+        assert_eq!(picked, version("0.8.10"));
+        assert!(!needs_install, "an installed match should never trigger a download");
+    }
+
+    #[test]
+    fn falls_back_to_the_newest_known_version_when_nothing_is_installed() {
+        let (picked, needs_install) =
+            pick_version(&req("^0.8.0"), &[], &[version("0.8.10"), version("0.8.17")], false).unwrap();
+
+        assert_eq!(picked, version("0.8.17"));
+        assert!(needs_install);
+    }
+
+    #[test]
+    fn offline_with_no_installed_match_is_an_error() {
+        let installed = vec![version("0.7.6")];
+        let known = vec![version("0.7.6"), version("0.8.17")];
+
+        let result = pick_version(&req("^0.8.0"), &installed, &known, true);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn offline_with_an_installed_match_still_succeeds() {
+        let installed = vec![version("0.8.10")];
+
+        let (picked, needs_install) = pick_version(&req("^0.8.0"), &installed, &[], true).unwrap();
+
+        assert_eq!(picked, version("0.8.10"));
+        assert!(!needs_install);
+    }
+
+    #[test]
+    fn no_known_release_satisfies_the_request_is_an_error() {
+        let result = pick_version(&req("^0.9.0"), &[], &[version("0.8.17")], false);
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! Project-wide configuration loaded from a `foundry.toml` file at the git
+//! root, merged with environment variables and CLI flags.
+//!
+//! Resolution order (lowest to highest precedence):
+//! built-in defaults -> `foundry.toml` -> environment variables -> CLI flags.
+//! The file sets project-wide defaults; `structopt` already folds env vars
+//! into the CLI args before we ever see them, so a profile only needs to
+//! fill in whatever is still unset once the flags are parsed.
+
+use evm_adapters::EvmVersion;
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::Path, path::PathBuf, str::FromStr};
+
+/// Name of the project config file, resolved relative to the git root.
+pub const CONFIG_FILE_NAME: &str = "foundry.toml";
+
+/// The profile used when none is selected via `--profile` or
+/// `FOUNDRY_PROFILE`.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// A single profile's worth of overrides.
+///
+/// Every field is optional so that a profile only needs to mention the
+/// values it cares about; anything left unset falls through to the next
+/// lower-precedence source.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Profile {
+    pub src: Option<PathBuf>,
+    pub out: Option<PathBuf>,
+    pub libs: Option<Vec<PathBuf>>,
+    /// Raw remapping strings, parsed lazily by [`Profile::remappings`]; a
+    /// bad entry panics with the remapping string only, not the profile name.
+    pub remappings: Option<Vec<String>>,
+    pub evm_version: Option<EvmVersion>,
+    pub no_auto_detect: Option<bool>,
+    pub ignored_error_codes: Option<Vec<u64>>,
+    // The `Env` block (gas_limit, chain_id, coinbase, ...) intentionally
+    // isn't overridable here yet -- nothing in this crate consumes `Env`
+    // until the `test`/`run` commands exist. Add the overrides back here,
+    // alongside a `Profile::apply_env`, once one of those lands.
+}
+
+impl Profile {
+    /// Parses the profile's raw remapping strings, panicking with the same
+    /// message style as [`crate::cmd::build`] uses for CLI/env remappings.
+    pub fn remappings(&self) -> Vec<ethers::solc::remappings::Remapping> {
+        self.remappings
+            .iter()
+            .flatten()
+            .map(|s| {
+                ethers::solc::remappings::Remapping::from_str(s)
+                    .unwrap_or_else(|_| panic!("could not parse remapping: {}", s))
+            })
+            .collect()
+    }
+}
+
+/// Parsed `foundry.toml`: a map of profile name to [`Profile`], e.g.
+///
+/// ```toml
+/// [default]
+/// src = "src"
+/// out = "out"
+/// libs = ["lib"]
+///
+/// [ci]
+/// no_auto_detect = true
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Config(BTreeMap<String, Profile>);
+
+impl Config {
+    /// Reads and parses `foundry.toml` from `root`, if present. A missing
+    /// file is not an error -- it simply yields an empty config, so
+    /// defaults and CLI flags still apply on their own.
+    pub fn load(root: &Path) -> eyre::Result<Config> {
+        let path = root.join(CONFIG_FILE_NAME);
+        if !path.exists() {
+            return Ok(Config::default())
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        let config = toml::from_str(&contents)?;
+        Ok(config)
+    }
+
+    /// Returns the named profile, or an empty one if the file doesn't
+    /// define it.
+    pub fn profile(&self, name: &str) -> Profile {
+        self.0.get(name).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_profile_falls_back_to_empty() {
+        let config = Config::default();
+        let profile = config.profile(DEFAULT_PROFILE);
+        assert!(profile.src.is_none());
+        assert!(profile.no_auto_detect.is_none());
+    }
+
+    #[test]
+    fn default_and_named_profiles_parse_independently() {
+        let toml = r#"
+            [default]
+            src = "src"
+            out = "out"
+            evm_version = "berlin"
+
+            [ci]
+            no_auto_detect = true
+            ignored_error_codes = [1878, 5667]
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+
+        let default = config.profile(DEFAULT_PROFILE);
+        assert_eq!(default.src, Some(PathBuf::from("src")));
+        assert_eq!(default.out, Some(PathBuf::from("out")));
+        assert!(matches!(default.evm_version, Some(EvmVersion::Berlin)));
+        // `ci` doesn't feed into `default` -- profiles never merge with
+        // each other, only with CLI flags/defaults at the call site.
+        assert!(default.no_auto_detect.is_none());
+
+        let ci = config.profile("ci");
+        assert_eq!(ci.no_auto_detect, Some(true));
+        assert_eq!(ci.ignored_error_codes, Some(vec![1878, 5667]));
+        assert!(ci.src.is_none());
+
+        // an unknown profile name is never an error -- it just has nothing set.
+        assert!(config.profile("doesnt-exist").src.is_none());
+    }
+
+    #[test]
+    fn remappings_are_parsed_from_profile_strings() {
+        let mut profile = Profile::default();
+        profile.remappings = Some(vec!["ds-test/=lib/ds-test/src/".to_string()]);
+
+        let remappings = profile.remappings();
+
+        assert_eq!(remappings.len(), 1);
+    }
+}
@@ -0,0 +1,18 @@
+//! Foundry's CLI building blocks: the [`Cmd`] trait implemented by every
+//! subcommand, plus the config and helpers shared across them.
+
+pub mod cache;
+pub mod cmd;
+pub mod compile;
+pub mod config;
+pub mod utils;
+
+/// A runnable CLI subcommand.
+///
+/// Implementors describe their flags via `#[derive(StructOpt)]` and produce
+/// whatever result is useful to downstream consumers (e.g. a compiled
+/// project, a set of test results) from [`Cmd::run`].
+pub trait Cmd: structopt::StructOpt + Sized {
+    type Output;
+    fn run(self) -> eyre::Result<Self::Output>;
+}
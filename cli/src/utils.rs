@@ -0,0 +1,12 @@
+//! Shared utilities used across subcommands.
+
+use std::path::PathBuf;
+
+/// Finds the root of the git repository containing the current directory by
+/// shelling out to `git rev-parse --show-toplevel`.
+pub fn find_git_root_path() -> eyre::Result<PathBuf> {
+    let output =
+        std::process::Command::new("git").arg("rev-parse").arg("--show-toplevel").output()?;
+    let path = std::str::from_utf8(&output.stdout)?.trim();
+    Ok(PathBuf::from(path))
+}
@@ -0,0 +1,368 @@
+//! `Env`, `EvmVersion`, `EvmType`, and the `EvmOpts` that ties them
+//! together with an optional fork source.
+
+use ethers::types::Address;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+#[cfg(feature = "evmodin-evm")]
+use evmodin::util::mocked_host::MockedHost;
+#[cfg(feature = "evmodin-evm")]
+use evmodin::Revision;
+#[cfg(feature = "sputnik-evm")]
+use sputnik::backend::MemoryVicinity;
+#[cfg(feature = "sputnik-evm")]
+use sputnik::Config;
+
+#[derive(Clone, Debug)]
+pub enum EvmType {
+    #[cfg(feature = "sputnik-evm")]
+    Sputnik,
+    #[cfg(feature = "evmodin-evm")]
+    EvmOdin,
+}
+
+impl FromStr for EvmType {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            #[cfg(feature = "sputnik-evm")]
+            "sputnik" => EvmType::Sputnik,
+            #[cfg(feature = "evmodin-evm")]
+            "evmodin" => EvmType::EvmOdin,
+            other => eyre::bail!("unknown EVM type {}", other),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum EvmVersion {
+    Frontier,
+    Istanbul,
+    Berlin,
+    London,
+}
+
+impl EvmVersion {
+    #[cfg(feature = "sputnik-evm")]
+    pub fn sputnik_cfg(self) -> Config {
+        use EvmVersion::*;
+        match self {
+            Frontier => Config::frontier(),
+            Istanbul => Config::istanbul(),
+            Berlin => Config::berlin(),
+            London => Config::london(),
+        }
+    }
+
+    #[cfg(feature = "evmodin-evm")]
+    pub fn evmodin_cfg(self) -> Revision {
+        use EvmVersion::*;
+        match self {
+            Frontier => Revision::Frontier,
+            Istanbul => Revision::Istanbul,
+            Berlin => Revision::Berlin,
+            London => Revision::London,
+        }
+    }
+}
+
+impl FromStr for EvmVersion {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use EvmVersion::*;
+        Ok(match s.to_lowercase().as_str() {
+            "frontier" => Frontier,
+            "istanbul" => Istanbul,
+            "berlin" => Berlin,
+            "london" => London,
+            _ => eyre::bail!("unsupported evm version: {}", s),
+        })
+    }
+}
+
+impl std::fmt::Display for EvmVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use EvmVersion::*;
+        let s = match self {
+            Frontier => "frontier",
+            Istanbul => "istanbul",
+            Berlin => "berlin",
+            London => "london",
+        };
+        f.write_str(s)
+    }
+}
+
+// lets `evm_version` be set from a `foundry.toml` profile, reusing the same
+// parsing rules as the CLI flag.
+impl<'de> serde::Deserialize<'de> for EvmVersion {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        EvmVersion::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, StructOpt)]
+pub struct Env {
+    // structopt does not let use `u64::MAX`:
+    // https://doc.rust-lang.org/std/primitive.u64.html#associatedconstant.MAX
+    #[structopt(help = "the block gas limit", long, default_value = "18446744073709551615")]
+    pub gas_limit: u64,
+
+    #[structopt(help = "the chainid opcode value", long, default_value = "1")]
+    pub chain_id: u64,
+
+    #[structopt(help = "the tx.gasprice value during EVM execution", long, default_value = "0")]
+    pub gas_price: u64,
+
+    #[structopt(help = "the base fee in a block", long, default_value = "0")]
+    pub block_base_fee_per_gas: u64,
+
+    #[structopt(
+        help = "the tx.origin value during EVM execution",
+        long,
+        default_value = "0x0000000000000000000000000000000000000000"
+    )]
+    pub tx_origin: Address,
+
+    #[structopt(
+    help = "the block.coinbase value during EVM execution",
+    long,
+    // TODO: It'd be nice if we could use Address::zero() here.
+    default_value = "0x0000000000000000000000000000000000000000"
+    )]
+    pub block_coinbase: Address,
+    #[structopt(
+        help = "the block.timestamp value during EVM execution",
+        long,
+        default_value = "0",
+        env = "DAPP_TEST_TIMESTAMP"
+    )]
+    pub block_timestamp: u64,
+
+    #[structopt(help = "the block.number value during EVM execution", long, default_value = "0")]
+    #[structopt(env = "DAPP_TEST_NUMBER")]
+    pub block_number: u64,
+
+    #[structopt(
+        help = "the block.difficulty value during EVM execution",
+        long,
+        default_value = "0"
+    )]
+    pub block_difficulty: u64,
+
+    #[structopt(help = "the block.gaslimit value during EVM execution", long)]
+    pub block_gas_limit: Option<u64>,
+    // TODO: Add configuration option for base fee.
+}
+
+impl Env {
+    #[cfg(feature = "sputnik-evm")]
+    pub fn sputnik_state(&self) -> MemoryVicinity {
+        MemoryVicinity {
+            chain_id: self.chain_id.into(),
+
+            gas_price: self.gas_price.into(),
+            origin: self.tx_origin,
+
+            block_coinbase: self.block_coinbase,
+            block_number: self.block_number.into(),
+            block_timestamp: self.block_timestamp.into(),
+            block_difficulty: self.block_difficulty.into(),
+            block_base_fee_per_gas: self.block_base_fee_per_gas.into(),
+            block_gas_limit: self.block_gas_limit.unwrap_or(self.gas_limit).into(),
+            block_hashes: Vec::new(),
+        }
+    }
+
+    #[cfg(feature = "evmodin-evm")]
+    pub fn evmodin_state(&self) -> MockedHost {
+        let mut host = MockedHost::default();
+
+        host.tx_context.chain_id = self.chain_id.into();
+        host.tx_context.tx_gas_price = self.gas_price.into();
+        host.tx_context.tx_origin = self.tx_origin;
+        host.tx_context.block_coinbase = self.block_coinbase;
+        host.tx_context.block_number = self.block_number;
+        host.tx_context.block_timestamp = self.block_timestamp;
+        host.tx_context.block_difficulty = self.block_difficulty.into();
+        host.tx_context.block_gas_limit = self.block_gas_limit.unwrap_or(self.gas_limit);
+
+        host
+    }
+}
+
+/// Where to source EVM state from: in-memory (the default), or forked from
+/// a live chain via JSON-RPC.
+#[derive(Debug, Clone, StructOpt)]
+pub struct ForkOpts {
+    #[structopt(help = "fork from the state of a remote JSON-RPC endpoint", long = "fork-url")]
+    pub fork_url: Option<String>,
+
+    #[structopt(
+        help = "the block to fork from; defaults to the latest block",
+        long = "fork-block"
+    )]
+    pub fork_block: Option<u64>,
+}
+
+/// Bundles everything needed to spin up an EVM: the on-chain environment,
+/// the hardfork to target, which backend implementation to run, and
+/// (optionally) a remote RPC to fork state from.
+///
+/// Building one `EvmOpts` and calling its `*_state`/`*_cfg` constructors
+/// per-test is cheap for in-memory state, which is the prerequisite for
+/// running each test in its own EVM in parallel with rayon instead of
+/// sharing one mutable instance. With `fork.fork_url` set, every
+/// `sputnik_state` call fetches the block and chain id over RPC again.
+#[derive(Debug, Clone, StructOpt)]
+pub struct EvmOpts {
+    #[structopt(flatten)]
+    pub env: Env,
+
+    #[structopt(help = "the EVM type to use", long = "evm-type", default_value = "sputnik")]
+    pub evm_type: EvmType,
+
+    #[structopt(help = "choose the evm version", long, default_value = "london")]
+    pub evm_version: EvmVersion,
+
+    #[structopt(flatten)]
+    pub fork: ForkOpts,
+}
+
+impl EvmOpts {
+    /// Builds the sputnik vicinity this `EvmOpts` describes: either the
+    /// in-memory state from `env`, or state forked from `fork.fork_url` at
+    /// `fork.fork_block` (the latest block if unset).
+    #[cfg(feature = "sputnik-evm")]
+    pub fn sputnik_state(&self) -> eyre::Result<MemoryVicinity> {
+        match &self.fork.fork_url {
+            Some(url) => self.forked_sputnik_state(url),
+            None => Ok(self.env.sputnik_state()),
+        }
+    }
+
+    #[cfg(feature = "sputnik-evm")]
+    fn forked_sputnik_state(&self, url: &str) -> eyre::Result<MemoryVicinity> {
+        use ethers::{
+            providers::{Http, Middleware, Provider},
+            types::{BlockId, BlockNumber},
+        };
+
+        let provider = Provider::<Http>::try_from(url)?;
+        let rt = tokio::runtime::Runtime::new()?;
+
+        let block_id = match self.fork.fork_block {
+            Some(number) => BlockId::Number(BlockNumber::Number(number.into())),
+            None => BlockId::Number(BlockNumber::Latest),
+        };
+        let block = rt
+            .block_on(provider.get_block(block_id))?
+            .ok_or_else(|| eyre::eyre!("could not fetch block {:?} from {}", block_id, url))?;
+        let chain_id = rt.block_on(provider.get_chainid())?;
+
+        let mut vicinity = self.env.sputnik_state();
+        overlay_forked_block(&mut vicinity, chain_id, &block);
+        Ok(vicinity)
+    }
+
+    /// Returns the `(Config, MemoryVicinity)` pair a fresh sputnik
+    /// executor needs. Each call yields an independent EVM's worth of
+    /// state; without a fork URL it is cheap to call repeatedly, with one
+    /// each call starts a runtime and makes RPC requests.
+    #[cfg(feature = "sputnik-evm")]
+    pub fn sputnik_executor_state(&self) -> eyre::Result<(Config, MemoryVicinity)> {
+        Ok((self.evm_version.clone().sputnik_cfg(), self.sputnik_state()?))
+    }
+}
+
+/// Overlays the fetched block/chain data onto `vicinity` in place. Split out
+/// of [`EvmOpts::forked_sputnik_state`] so the field-by-field override rules
+/// -- including that a missing `base_fee_per_gas` (pre-London chains) leaves
+/// whatever `vicinity` already had, rather than zeroing it -- can be tested
+/// without an RPC connection.
+#[cfg(feature = "sputnik-evm")]
+fn overlay_forked_block(
+    vicinity: &mut MemoryVicinity,
+    chain_id: ethers::types::U256,
+    block: &ethers::types::Block<ethers::types::H256>,
+) {
+    vicinity.chain_id = chain_id;
+    vicinity.block_number = block.number.unwrap_or_default().as_u64().into();
+    vicinity.block_timestamp = block.timestamp;
+    vicinity.block_difficulty = block.difficulty;
+    vicinity.block_gas_limit = block.gas_limit;
+    if let Some(base_fee) = block.base_fee_per_gas {
+        vicinity.block_base_fee_per_gas = base_fee;
+    }
+}
+
+#[cfg(all(test, feature = "sputnik-evm"))]
+mod tests {
+    use super::*;
+    use ethers::types::{Block, H256, U256, U64};
+
+    fn base_env() -> Env {
+        Env {
+            gas_limit: u64::MAX,
+            chain_id: 1,
+            gas_price: 0,
+            block_base_fee_per_gas: 42,
+            tx_origin: Address::zero(),
+            block_coinbase: Address::zero(),
+            block_timestamp: 0,
+            block_number: 0,
+            block_difficulty: 0,
+            block_gas_limit: None,
+        }
+    }
+
+    #[test]
+    fn overlay_replaces_chain_id_and_block_fields() {
+        let mut vicinity = base_env().sputnik_state();
+        let block = Block::<H256> {
+            number: Some(U64::from(100)),
+            timestamp: U256::from(1_000_000),
+            difficulty: U256::from(123),
+            gas_limit: U256::from(30_000_000),
+            base_fee_per_gas: Some(U256::from(7)),
+            ..Default::default()
+        };
+
+        overlay_forked_block(&mut vicinity, U256::from(5), &block);
+
+        assert_eq!(vicinity.chain_id, U256::from(5));
+        assert_eq!(vicinity.block_number, U256::from(100));
+        assert_eq!(vicinity.block_timestamp, U256::from(1_000_000));
+        assert_eq!(vicinity.block_difficulty, U256::from(123));
+        assert_eq!(vicinity.block_gas_limit, U256::from(30_000_000));
+        assert_eq!(vicinity.block_base_fee_per_gas, U256::from(7));
+    }
+
+    #[test]
+    fn overlay_leaves_base_fee_untouched_on_pre_london_blocks() {
+        let mut vicinity = base_env().sputnik_state();
+        let original_base_fee = vicinity.block_base_fee_per_gas;
+        let block = Block::<H256> { base_fee_per_gas: None, ..Default::default() };
+
+        overlay_forked_block(&mut vicinity, U256::from(1), &block);
+
+        assert_eq!(vicinity.block_base_fee_per_gas, original_base_fee);
+    }
+
+    #[test]
+    fn overlay_defaults_missing_block_number_to_zero() {
+        let mut vicinity = base_env().sputnik_state();
+        let block = Block::<H256> { number: None, ..Default::default() };
+
+        overlay_forked_block(&mut vicinity, U256::from(1), &block);
+
+        assert_eq!(vicinity.block_number, U256::zero());
+    }
+}
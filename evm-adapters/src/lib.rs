@@ -0,0 +1,13 @@
+//! Shared EVM configuration and adapters consumed by every command that
+//! needs to spin up an EVM.
+//!
+//! [`EvmOpts`] bundles the on-chain environment ([`Env`]), the hardfork to
+//! target ([`EvmVersion`]), which backend implementation to run
+//! ([`EvmType`]), and an optional remote RPC to fork state from. Building
+//! this once and handing it to many independent runs is what lets a
+//! runner spin up one EVM per test instead of sharing a single mutable
+//! instance.
+
+pub mod evm_opts;
+
+pub use evm_opts::{Env, EvmOpts, EvmType, EvmVersion, ForkOpts};